@@ -1,6 +1,11 @@
 use crate::error::Error;
 use shakmaty::{san::SanPlus, Chess, Move, Position};
 
+/// Format tag written as the first byte of a packed move blob. Legacy blobs
+/// store one legal-move index per ply, and the first ply never has more than
+/// 20 legal moves, so any tag `>= 20` unambiguously marks the packed layout.
+const PACKED_FORMAT_TAG: u8 = 0xFF;
+
 pub fn encode_move(m: &Move, chess: &Chess) -> Result<u8, Error> {
     let moves = chess.legal_moves();
     Ok(moves.iter().position(|x| x == m).unwrap() as u8)
@@ -11,17 +16,135 @@ pub fn decode_move(byte: u8, chess: &Chess) -> Option<Move> {
     legal_moves.get(byte as usize).cloned()
 }
 
-pub fn decode_moves(moves_bytes: Vec<u8>) -> Result<String, Error> {
+/// Accumulates bits MSB-first into a byte vector.
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u8,
+    filled: u8,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            current: 0,
+            filled: 0,
+        }
+    }
+
+    /// Writes the low `count` bits of `value`, most-significant bit first.
+    fn write(&mut self, value: usize, count: u8) {
+        for i in (0..count).rev() {
+            let bit = ((value >> i) & 1) as u8;
+            self.current |= bit << (7 - self.filled);
+            self.filled += 1;
+            if self.filled == 8 {
+                self.bytes.push(self.current);
+                self.current = 0;
+                self.filled = 0;
+            }
+        }
+    }
+
+    /// Flushes any partial byte (zero-padded) and returns the buffer.
+    fn finish(mut self) -> Vec<u8> {
+        if self.filled > 0 {
+            self.bytes.push(self.current);
+        }
+        self.bytes
+    }
+}
+
+/// Replays the bits produced by a [`BitWriter`], MSB-first.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Reads `count` bits and returns them as an integer, MSB-first.
+    fn read(&mut self, count: u8) -> usize {
+        let mut value = 0;
+        for _ in 0..count {
+            let byte = self.bytes[self.pos / 8];
+            let bit = (byte >> (7 - (self.pos % 8))) & 1;
+            value = (value << 1) | bit as usize;
+            self.pos += 1;
+        }
+        value
+    }
+}
+
+/// Number of bits needed to index into a list of `n` elements.
+/// A forced move (`n == 1`) needs no bits at all.
+fn bits_for(n: usize) -> u8 {
+    if n <= 1 {
+        0
+    } else {
+        (usize::BITS - (n - 1).leading_zeros()) as u8
+    }
+}
+
+/// Encodes a line of moves into a bit-packed blob, spending only
+/// `ceil(log2(N))` bits per ply where `N` is the number of legal moves in the
+/// current position. The blob is prefixed with [`PACKED_FORMAT_TAG`] and the
+/// ply count (4 bytes, big-endian) so [`decode_moves`] can tell it apart from
+/// the legacy one-byte-per-move layout and knows where the stream ends rather
+/// than decoding trailing zero padding as spurious moves.
+pub fn encode_moves(moves: &[Move]) -> Vec<u8> {
+    let mut chess = Chess::default();
+    let mut writer = BitWriter::new();
+    for m in moves {
+        let legal_moves = chess.legal_moves();
+        let index = legal_moves.iter().position(|x| x == m).unwrap();
+        writer.write(index, bits_for(legal_moves.len()));
+        chess.play_unchecked(m);
+    }
+    let mut out = vec![PACKED_FORMAT_TAG];
+    out.extend((moves.len() as u32).to_be_bytes());
+    out.extend(writer.finish());
+    out
+}
+
+fn decode_packed(bytes: &[u8], ply_count: u32) -> Result<String, Error> {
+    let mut chess = Chess::default();
+    let mut reader = BitReader::new(bytes);
+    let mut moves = Vec::new();
+    for _ in 0..ply_count {
+        let legal_moves = chess.legal_moves();
+        let index = reader.read(bits_for(legal_moves.len()));
+        let m = legal_moves.get(index).cloned().ok_or(Error::NoMatchFound)?;
+        let san = SanPlus::from_move_and_play_unchecked(&mut chess, &m);
+        moves.push(san.to_string());
+    }
+    Ok(moves.join(" "))
+}
+
+fn decode_legacy(moves_bytes: &[u8]) -> Result<String, Error> {
     let mut chess = Chess::default();
     let mut moves = Vec::new();
     for byte in moves_bytes {
-        let m = decode_move(byte, &chess).unwrap();
+        let m = decode_move(*byte, &chess).unwrap();
         let san = SanPlus::from_move_and_play_unchecked(&mut chess, &m);
         moves.push(san.to_string());
     }
     Ok(moves.join(" "))
 }
 
+pub fn decode_moves(moves_bytes: Vec<u8>) -> Result<String, Error> {
+    match moves_bytes.split_first() {
+        Some((&PACKED_FORMAT_TAG, rest)) if rest.len() >= 4 => {
+            let ply_count = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]);
+            decode_packed(&rest[4..], ply_count)
+        }
+        _ => decode_legacy(&moves_bytes),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -56,4 +179,47 @@ mod tests {
         let m2 = decode_move(byte, &chess).unwrap();
         assert_eq!(m, m2);
     }
+
+    #[test]
+    fn test_packed_roundtrip() {
+        let mut chess = Chess::default();
+        let mut moves = Vec::new();
+        let mut sans = Vec::new();
+        for m in [
+            Move::Normal {
+                role: Role::Pawn,
+                from: Square::E2,
+                to: Square::E4,
+                capture: None,
+                promotion: None,
+            },
+            Move::Normal {
+                role: Role::Pawn,
+                from: Square::E7,
+                to: Square::E5,
+                capture: None,
+                promotion: None,
+            },
+            Move::Normal {
+                role: Role::Knight,
+                from: Square::G1,
+                to: Square::F3,
+                capture: None,
+                promotion: None,
+            },
+        ] {
+            sans.push(SanPlus::from_move_and_play_unchecked(&mut chess, &m).to_string());
+            moves.push(m);
+        }
+
+        let blob = encode_moves(&moves);
+        assert_eq!(blob[0], PACKED_FORMAT_TAG);
+        assert_eq!(decode_moves(blob).unwrap(), sans.join(" "));
+    }
+
+    #[test]
+    fn test_legacy_still_decodes() {
+        // One byte per ply, no format tag: 1.e4 e5.
+        assert_eq!(decode_moves(vec![12, 12]).unwrap(), "e4 e5");
+    }
 }
@@ -13,6 +13,15 @@ pub struct Puzzle {
     pub rating_deviation: i32,
     pub popularity: i32,
     pub nb_plays: i32,
+    pub themes: String,
+}
+
+impl Puzzle {
+    /// Splits the space-separated theme tag string (`"fork pin endgame"`) into
+    /// its individual motif tags.
+    pub fn theme_tags(&self) -> Vec<&str> {
+        self.themes.split_whitespace().collect()
+    }
 }
 
 #[derive(Default, Debug, Queryable, Serialize, Deserialize, Identifiable, Clone)]
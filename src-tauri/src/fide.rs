@@ -1,8 +1,12 @@
 use std::{
+    collections::{HashMap, HashSet},
     fs::{remove_file, File},
     io::{BufReader, BufWriter},
+    sync::RwLock as StdRwLock,
 };
 
+use once_cell::sync::Lazy;
+
 use bincode::{config, Decode, Encode};
 use quick_xml::de::from_reader;
 use serde::{Deserialize, Deserializer, Serialize};
@@ -177,3 +181,128 @@ pub async fn find_fide_player(
         Err(Error::NoMatchFound)
     }
 }
+
+/// A shortlisted FIDE player together with its jaro-winkler similarity to the
+/// query, returned by [`find_fide_players`] for disambiguation.
+#[derive(Debug, Serialize, Type, Clone)]
+pub struct FideMatch {
+    pub player: FidePlayer,
+    pub score: f64,
+}
+
+/// Lowercased token/trigram map over player names, built on first use so
+/// queries only score the candidates that share a token or trigram with the
+/// query instead of the entire FIDE list.
+#[derive(Default)]
+struct FideIndex {
+    /// Number of players the index was built from; a different player count
+    /// triggers a rebuild.
+    len: usize,
+    tokens: HashMap<String, Vec<usize>>,
+}
+
+/// Splits a name into lowercased whitespace/comma tokens plus the trigrams of
+/// each token, so both exact words and close spellings retrieve candidates.
+fn name_keys(name: &str) -> HashSet<String> {
+    let mut keys = HashSet::new();
+    for token in name.to_lowercase().split(|c: char| c.is_whitespace() || c == ',') {
+        if token.is_empty() {
+            continue;
+        }
+        keys.insert(token.to_string());
+        let chars: Vec<char> = token.chars().collect();
+        if chars.len() < 3 {
+            continue;
+        }
+        for window in chars.windows(3) {
+            keys.insert(window.iter().collect());
+        }
+    }
+    keys
+}
+
+impl FideIndex {
+    fn build(players: &[FidePlayer]) -> Self {
+        let mut tokens: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, player) in players.iter().enumerate() {
+            for key in name_keys(&player.name) {
+                tokens.entry(key).or_default().push(i);
+            }
+        }
+        Self {
+            len: players.len(),
+            tokens,
+        }
+    }
+
+    /// Returns the indices of players sharing at least one token/trigram with
+    /// the query.
+    fn candidates(&self, query: &str) -> HashSet<usize> {
+        let mut candidates = HashSet::new();
+        for key in name_keys(query) {
+            if let Some(indices) = self.tokens.get(&key) {
+                candidates.extend(indices.iter().copied());
+            }
+        }
+        candidates
+    }
+}
+
+static FIDE_INDEX: Lazy<StdRwLock<FideIndex>> = Lazy::new(|| StdRwLock::new(FideIndex::default()));
+
+/// Returns the top-`limit` FIDE players most similar to `player` whose score is
+/// at least `threshold`, ranked by jaro-winkler similarity. Only candidates
+/// sharing a name token or trigram with the query are scored, using a
+/// lazily-built in-memory index that is rebuilt whenever the number of loaded
+/// players changes; a reloaded list with the same number of players keeps the
+/// existing index.
+#[tauri::command]
+#[specta::specta]
+pub async fn find_fide_players(
+    player: String,
+    limit: usize,
+    threshold: f64,
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<Vec<FideMatch>, Error> {
+    let fide_players = state.fide_players.read().await;
+
+    if fide_players.is_empty() {
+        drop(fide_players);
+        let config = config::standard();
+        let fide_path = resolve_path(
+            &app.config(),
+            app.package_info(),
+            &app.env(),
+            "fide.bin",
+            Some(BaseDirectory::AppData),
+        )?;
+
+        if let Ok(f) = File::open(&fide_path) {
+            let mut fide_players = state.fide_players.write().await;
+            *fide_players = bincode::decode_from_reader(BufReader::new(f), config)?;
+        }
+    }
+
+    let fide_players = state.fide_players.read().await;
+
+    // (Re)build the name index whenever the number of loaded players changed.
+    if FIDE_INDEX.read().unwrap().len != fide_players.len() {
+        *FIDE_INDEX.write().unwrap() = FideIndex::build(&fide_players);
+    }
+
+    let candidates = FIDE_INDEX.read().unwrap().candidates(&player);
+    let mut matches: Vec<FideMatch> = candidates
+        .into_iter()
+        .filter_map(|i| fide_players.get(i).map(|p| (p, jaro_winkler(&player, &p.name))))
+        .filter(|(_, score)| *score >= threshold)
+        .map(|(p, score)| FideMatch {
+            player: p.clone(),
+            score,
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    matches.truncate(limit);
+    Ok(matches)
+}
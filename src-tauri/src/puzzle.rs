@@ -1,6 +1,9 @@
 use std::{collections::VecDeque, path::PathBuf, sync::Mutex};
 
-use diesel::{dsl::sql, sql_types::Bool, Connection, ExpressionMethods, QueryDsl, RunQueryDsl};
+use diesel::{
+    dsl::sql, sql_query, sql_types::Bool, Connection, ExpressionMethods, QueryDsl,
+    QueryableByName, RunQueryDsl, SqliteConnection,
+};
 use once_cell::sync::Lazy;
 use serde::Serialize;
 use tauri::{
@@ -19,6 +22,7 @@ struct PuzzleCache {
     counter: usize,
     min_rating: usize,
     max_rating: usize,
+    themes: Vec<String>,
 }
 
 impl PuzzleCache {
@@ -28,6 +32,7 @@ impl PuzzleCache {
             counter: 0,
             min_rating: 0,
             max_rating: 0,
+            themes: Vec::new(),
         }
     }
 
@@ -36,19 +41,31 @@ impl PuzzleCache {
         file: &str,
         min_rating: usize,
         max_rating: usize,
+        themes: &[String],
     ) -> Result<(), Error> {
         if self.cache.is_empty()
             || self.min_rating != min_rating
             || self.max_rating != max_rating
+            || self.themes != themes
             || self.counter >= 20
         {
             self.cache.clear();
             self.counter = 0;
 
             let mut db = diesel::SqliteConnection::establish(file).expect("open database");
-            let new_puzzles = puzzles::table
+            let mut query = puzzles::table
                 .filter(puzzles::rating.le(max_rating as i32))
                 .filter(puzzles::rating.ge(min_rating as i32))
+                .into_boxed();
+            // Match each requested theme as a whole tag by padding the
+            // space-separated column so `mateIn2` never matches `mateIn20`.
+            for theme in themes {
+                let pattern = theme.replace('\'', "''");
+                query = query.filter(sql::<Bool>(&format!(
+                    "(' ' || themes || ' ') LIKE '% {pattern} %'"
+                )));
+            }
+            let new_puzzles = query
                 .order(sql::<Bool>("RANDOM()"))
                 .limit(20)
                 .load::<Puzzle>(&mut db)?;
@@ -56,6 +73,7 @@ impl PuzzleCache {
             self.cache = new_puzzles.into_iter().collect();
             self.min_rating = min_rating;
             self.max_rating = max_rating;
+            self.themes = themes.to_vec();
         }
 
         Ok(())
@@ -72,11 +90,183 @@ impl PuzzleCache {
 }
 
 #[tauri::command]
-pub fn get_puzzle(file: String, min_rating: usize, max_rating: usize) -> Result<Puzzle, Error> {
+pub fn get_puzzle(
+    file: String,
+    min_rating: usize,
+    max_rating: usize,
+    themes: Vec<String>,
+) -> Result<Puzzle, Error> {
+    static PUZZLE_CACHE: Lazy<Mutex<PuzzleCache>> = Lazy::new(|| Mutex::new(PuzzleCache::new()));
+
+    let mut cache = PUZZLE_CACHE.lock().unwrap();
+    cache.get_puzzles(&file, min_rating, max_rating, &themes)?;
+    cache.get_next_puzzle().ok_or(Error::NoPuzzles)
+}
+
+/// Glicko-2 system constant τ: constrains how far the volatility can change in
+/// one update. A small value (here 0.5) keeps volatility changes conservative.
+const GLICKO_TAU: f64 = 0.5;
+/// Conversion factor between the Glicko (r/RD) and Glicko-2 (μ/φ) scales.
+const GLICKO_SCALE: f64 = 173.7178;
+const DEFAULT_RATING: f64 = 1500.0;
+const DEFAULT_DEVIATION: f64 = 350.0;
+const DEFAULT_VOLATILITY: f64 = 0.06;
+
+/// The solver's persistent Glicko-2 rating, stored as a single row in the
+/// opened puzzle database.
+#[derive(Debug, Clone, Serialize, QueryableByName)]
+pub struct UserRating {
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub rating: f64,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub deviation: f64,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub volatility: f64,
+}
+
+impl Default for UserRating {
+    fn default() -> Self {
+        Self {
+            rating: DEFAULT_RATING,
+            deviation: DEFAULT_DEVIATION,
+            volatility: DEFAULT_VOLATILITY,
+        }
+    }
+}
+
+impl UserRating {
+    /// Updates this rating after a single attempt against a puzzle of the given
+    /// Glicko rating and deviation, following the standard Glicko-2 procedure.
+    /// `score` is 1.0 for a solved puzzle and 0.0 otherwise.
+    fn update(&mut self, puzzle_rating: f64, puzzle_deviation: f64, score: f64) {
+        let mu = (self.rating - DEFAULT_RATING) / GLICKO_SCALE;
+        let phi = self.deviation / GLICKO_SCALE;
+        let mu_j = (puzzle_rating - DEFAULT_RATING) / GLICKO_SCALE;
+        let phi_j = puzzle_deviation / GLICKO_SCALE;
+
+        let g = 1.0 / (1.0 + 3.0 * phi_j * phi_j / (std::f64::consts::PI * std::f64::consts::PI)).sqrt();
+        let e = 1.0 / (1.0 + (-g * (mu - mu_j)).exp());
+        let v = 1.0 / (g * g * e * (1.0 - e));
+        let delta = v * g * (score - e);
+
+        let sigma = self.volatility;
+        let a = (sigma * sigma).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            let num = ex * (delta * delta - phi * phi - v - ex);
+            let den = 2.0 * (phi * phi + v + ex).powi(2);
+            num / den - (x - a) / (GLICKO_TAU * GLICKO_TAU)
+        };
+
+        // Illinois algorithm to solve f(x) = 0.
+        let mut big_a = a;
+        let mut big_b = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * GLICKO_TAU) < 0.0 {
+                k += 1.0;
+            }
+            a - k * GLICKO_TAU
+        };
+
+        let mut f_a = f(big_a);
+        let mut f_b = f(big_b);
+        while (big_b - big_a).abs() > 1e-6 {
+            let c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+            let f_c = f(c);
+            if f_c * f_b <= 0.0 {
+                big_a = big_b;
+                f_a = f_b;
+            } else {
+                f_a /= 2.0;
+            }
+            big_b = c;
+            f_b = f_c;
+        }
+        let new_sigma = (big_a / 2.0).exp();
+
+        let phi_star = (phi * phi + new_sigma * new_sigma).sqrt();
+        let new_phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+        let new_mu = mu + new_phi * new_phi * g * (score - e);
+
+        self.rating = GLICKO_SCALE * new_mu + DEFAULT_RATING;
+        self.deviation = GLICKO_SCALE * new_phi;
+        self.volatility = new_sigma;
+    }
+}
+
+/// Ensures the single-row `user_rating` table exists and returns the current
+/// rating, or the Glicko-2 defaults when no rating has been stored yet (the
+/// defaults are not written here).
+fn load_user_rating(db: &mut SqliteConnection) -> Result<UserRating, Error> {
+    sql_query(
+        "CREATE TABLE IF NOT EXISTS user_rating (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            rating REAL NOT NULL,
+            deviation REAL NOT NULL,
+            volatility REAL NOT NULL
+        )",
+    )
+    .execute(db)?;
+
+    let rows = sql_query("SELECT rating, deviation, volatility FROM user_rating WHERE id = 1")
+        .load::<UserRating>(db)?;
+
+    Ok(rows.into_iter().next().unwrap_or_default())
+}
+
+fn save_user_rating(db: &mut SqliteConnection, rating: &UserRating) -> Result<(), Error> {
+    sql_query(
+        "INSERT INTO user_rating (id, rating, deviation, volatility)
+         VALUES (1, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET
+            rating = excluded.rating,
+            deviation = excluded.deviation,
+            volatility = excluded.volatility",
+    )
+    .bind::<diesel::sql_types::Double, _>(rating.rating)
+    .bind::<diesel::sql_types::Double, _>(rating.deviation)
+    .bind::<diesel::sql_types::Double, _>(rating.volatility)
+    .execute(db)?;
+    Ok(())
+}
+
+/// Records the result of a puzzle attempt and returns the updated user rating.
+/// `solved` is true only when the puzzle was solved on the first try.
+#[tauri::command]
+pub fn update_puzzle_rating(
+    file: String,
+    puzzle_rating: i32,
+    puzzle_deviation: i32,
+    solved: bool,
+) -> Result<UserRating, Error> {
+    let mut db = SqliteConnection::establish(&file).expect("open database");
+    let mut rating = load_user_rating(&mut db)?;
+    rating.update(
+        puzzle_rating as f64,
+        puzzle_deviation as f64,
+        if solved { 1.0 } else { 0.0 },
+    );
+    save_user_rating(&mut db, &rating)?;
+    Ok(rating)
+}
+
+/// Returns a puzzle centered on the solver's current rating, drawing from the
+/// `[r - RD, r + RD]` window so difficulty self-calibrates over a session.
+#[tauri::command]
+pub fn get_puzzle_rated(file: String) -> Result<Puzzle, Error> {
     static PUZZLE_CACHE: Lazy<Mutex<PuzzleCache>> = Lazy::new(|| Mutex::new(PuzzleCache::new()));
 
+    let rating = {
+        let mut db = SqliteConnection::establish(&file).expect("open database");
+        load_user_rating(&mut db)?
+    };
+    let min_rating = (rating.rating - rating.deviation).max(0.0) as usize;
+    let max_rating = (rating.rating + rating.deviation) as usize;
+
     let mut cache = PUZZLE_CACHE.lock().unwrap();
-    cache.get_puzzles(&file, min_rating, max_rating)?;
+    cache.get_puzzles(&file, min_rating, max_rating, &[])?;
     cache.get_next_puzzle().ok_or(Error::NoPuzzles)
 }
 
@@ -87,6 +277,7 @@ pub struct PuzzleDatabaseInfo {
     puzzle_count: usize,
     storage_size: usize,
     path: String,
+    themes: std::collections::HashMap<String, usize>,
 }
 
 #[tauri::command]
@@ -109,6 +300,17 @@ pub async fn get_puzzle_db_info(
 
     let puzzle_count = puzzles::table.count().get_result::<i64>(&mut db)? as usize;
 
+    // Tally the distinct motif tags so the UI can offer a themed training menu.
+    let mut themes: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    for tags in puzzles::table
+        .select(puzzles::themes)
+        .load::<String>(&mut db)?
+    {
+        for tag in tags.split_whitespace() {
+            *themes.entry(tag.to_string()).or_insert(0) += 1;
+        }
+    }
+
     let storage_size = path.metadata()?.len() as usize;
     let filename = path.file_name().expect("get filename").to_string_lossy();
 
@@ -118,5 +320,6 @@ pub async fn get_puzzle_db_info(
         puzzle_count,
         storage_size,
         path: path.to_string_lossy().to_string(),
+        themes,
     })
 }